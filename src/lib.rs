@@ -181,19 +181,119 @@ pub mod utils {
 /// [`eject`]: lifecycle::eject
 pub mod lifecycle {
 
+    use std::sync::atomic::Ordering;
     use std::thread;
+    use std::time::Duration;
 
-    use windows::Win32::System::LibraryLoader::FreeLibraryAndExitThread;
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::System::LibraryLoader::{
+        FreeLibrary, FreeLibraryAndExitThread, GetModuleHandleW,
+    };
+
+    use crate::tracing::{error, trace};
+
+    /// The render DLLs whose presence gates hook installation.
+    ///
+    /// A swap-chain vtable can only be discovered once one of these is
+    /// loaded in the target process.
+    pub(crate) const RENDER_DLLS: [PCWSTR; 4] =
+        [w!("d3d9.dll"), w!("d3d11.dll"), w!("d3d12.dll"), w!("opengl32.dll")];
+
+    /// Tunables for deferred hook installation.
+    ///
+    /// When the DLL is injected before the target's graphics runtime is
+    /// loaded (early injection, launchers), the render vtables can't be
+    /// discovered yet. The hook thread uses these values to wait for one
+    /// of the [supported render DLLs](RENDER_DLLS) to materialize before
+    /// installing the trampoline.
+    #[derive(Debug, Clone, Copy)]
+    pub struct HookConfig {
+        /// Number of polls after the first one before giving up on the hook.
+        pub max_retries: usize,
+        /// Interval to wait between polls.
+        pub retry_interval: Duration,
+    }
+
+    impl Default for HookConfig {
+        fn default() -> Self {
+            // A minute of 100ms polls is plenty for a renderer to come up.
+            Self { max_retries: 600, retry_interval: Duration::from_millis(100) }
+        }
+    }
+
+    /// Block until one of the supported render DLLs is loaded in the
+    /// current process, or the retry budget in `config` is exhausted.
+    ///
+    /// Returns `true` as soon as a render DLL is found, `false` if none
+    /// appeared within the first poll and `config.max_retries` retries
+    /// (`config.max_retries + 1` polls in all). It is invoked by the
+    /// [`hudhook`](crate::hudhook) macro before `Hooks::hook`, so the
+    /// vtable discovery only runs once the renderer is present.
+    ///
+    /// Note: this is a `GetModuleHandle` poll at a fixed interval; the
+    /// interval does not grow between attempts. An event-driven
+    /// `LdrRegisterDllNotification` trigger is not implemented; callers that
+    /// need tighter latency should lower [`HookConfig::retry_interval`].
+    pub fn wait_for_renderer(config: &HookConfig) -> bool {
+        for attempt in 0..=config.max_retries {
+            let loaded = RENDER_DLLS.iter().any(|name| unsafe {
+                GetModuleHandleW(*name).map(|module| !module.is_invalid()).unwrap_or(false)
+            });
+
+            if loaded {
+                trace!("render DLL found after {attempt} retries");
+                return true;
+            }
+
+            if attempt < config.max_retries {
+                thread::sleep(config.retry_interval);
+            }
+        }
+
+        error!("no supported render DLL loaded after {} attempts", config.max_retries + 1);
+        false
+    }
 
     /// Disable hooks and eject the DLL.
+    ///
+    /// Ejection is ordered to avoid unmapping code that is still running:
+    /// the hooks are removed first, ejection waits for any in-flight frame
+    /// (tracked by [`FrameGuard`](global_state::FrameGuard)) to drain out
+    /// of the trampolines for up to about one second (after which it
+    /// proceeds regardless), the references held on the target renderer DLL
+    /// and on our own module are released, and only then is the thread
+    /// torn down with `FreeLibraryAndExitThread`.
     pub fn eject() {
         thread::spawn(|| unsafe {
             crate::utils::free_console();
 
+            // Unhook first so no new frames can enter our trampolines.
             if let Some(mut hooks) = global_state::HOOKS.take() {
                 hooks.unhook();
             }
 
+            // Wait for any in-flight frame to actually drain out of the
+            // trampolines before we release the modules it may still be
+            // executing inside, bounded so a stuck frame can't hang
+            // ejection forever.
+            for _ in 0..1000 {
+                if global_state::IN_FLIGHT_FRAMES.load(Ordering::SeqCst) == 0 {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+
+            // Release the reference held on the target renderer DLL.
+            if let Some(renderer) = global_state::RENDERER_MODULE.take() {
+                let _ = FreeLibrary(renderer);
+            }
+
+            // Release the extra reference held on our own module.
+            if let Some(self_ref) = global_state::SELF_MODULE_REF.take() {
+                let _ = FreeLibrary(self_ref);
+            }
+
+            // Finally drop the last reference on our module and exit.
             if let Some(module) = global_state::MODULE.take() {
                 FreeLibraryAndExitThread(module, 0);
             }
@@ -212,13 +312,55 @@ pub mod lifecycle {
     pub mod global_state {
 
         use std::cell::OnceCell;
+        use std::sync::atomic::{AtomicUsize, Ordering};
 
+        use windows::core::PCWSTR;
         use windows::Win32::Foundation::HINSTANCE;
+        use windows::Win32::System::LibraryLoader::{
+            GetModuleHandleExW, GetModuleHandleW, LoadLibraryW,
+            GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS,
+        };
 
         use crate::hooks;
 
         pub(super) static mut MODULE: OnceCell<HINSTANCE> = OnceCell::new();
         pub(super) static mut HOOKS: OnceCell<Box<dyn hooks::Hooks>> = OnceCell::new();
+        pub(super) static mut SELF_MODULE_REF: OnceCell<HINSTANCE> = OnceCell::new();
+        pub(super) static mut RENDERER_MODULE: OnceCell<HINSTANCE> = OnceCell::new();
+        pub(super) static mut HOOKED_DLL: OnceCell<PCWSTR> = OnceCell::new();
+
+        /// Number of frames currently executing inside our trampolines.
+        pub(super) static IN_FLIGHT_FRAMES: AtomicUsize = AtomicUsize::new(0);
+
+        /// RAII marker that a frame is currently running inside a hooked
+        /// trampoline.
+        ///
+        /// A hooked render callback can construct one at its top;
+        /// [`eject`](super::eject) waits for the in-flight count to reach
+        /// zero before releasing the module references the frame may still
+        /// be executing inside. The backends in [`hooks`](crate::hooks) do
+        /// not construct one, so their frames are not counted.
+        pub struct FrameGuard(());
+
+        impl FrameGuard {
+            /// Register a frame as in-flight for the lifetime of the guard.
+            pub fn new() -> Self {
+                IN_FLIGHT_FRAMES.fetch_add(1, Ordering::SeqCst);
+                FrameGuard(())
+            }
+        }
+
+        impl Default for FrameGuard {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl Drop for FrameGuard {
+            fn drop(&mut self) {
+                IN_FLIGHT_FRAMES.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
 
         /// Please don't use me.
         pub fn set_module(module: HINSTANCE) {
@@ -227,6 +369,66 @@ pub mod lifecycle {
             }
         }
 
+        /// Record the render DLL the backend latched onto.
+        ///
+        /// A [`Hooks`](crate::hooks::Hooks) implementation can call this
+        /// from its `hook()` once it has picked a backend, so that
+        /// [`pin_modules`] references the exact module the trampoline runs
+        /// inside rather than guessing from the set of loaded DLLs. The
+        /// backends in [`hooks`](crate::hooks) do not call it, so for them
+        /// [`pin_modules`] references the first resident render DLL.
+        pub fn set_hooked_dll(name: PCWSTR) {
+            unsafe {
+                HOOKED_DLL.set(name).ok();
+            }
+        }
+
+        /// Take explicit references on the modules involved in hooking so
+        /// neither can be unmapped while a trampoline is still running.
+        ///
+        /// Holds an extra reference on our own module (via
+        /// `GetModuleHandleExW` from an address inside it) and on the
+        /// target renderer DLL (via `LoadLibraryW`). Both are released in
+        /// [`eject`](super::eject). Invoked by the
+        /// [`hudhook`](crate::hudhook) macro once hooks are installed.
+        pub fn pin_modules() {
+            unsafe {
+                // Reference our own module from an address we know lives
+                // inside it; this bumps the module's reference count.
+                let mut self_module = HINSTANCE::default();
+                if GetModuleHandleExW(
+                    GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS,
+                    PCWSTR(pin_modules as *const () as *const u16),
+                    &mut self_module,
+                )
+                .is_ok()
+                {
+                    SELF_MODULE_REF.set(self_module).ok();
+                }
+
+                // Reference the render DLL the hook latched onto. Prefer
+                // the module the backend recorded at `hook()` time; fall
+                // back to whichever supported render DLL is resident so a
+                // reference is still held if the backend didn't record one.
+                let hooked = HOOKED_DLL.get().copied();
+                for name in hooked.into_iter().chain(crate::lifecycle::RENDER_DLLS) {
+                    // Only reference a render DLL that is already resident;
+                    // `LoadLibraryW` would otherwise force-load an unrelated
+                    // runtime into the target.
+                    let resident =
+                        GetModuleHandleW(name).map(|m| !m.is_invalid()).unwrap_or(false);
+                    if resident {
+                        if let Ok(module) = LoadLibraryW(name) {
+                            if !module.is_invalid() {
+                                RENDERER_MODULE.set(module).ok();
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
         /// Please don't use me.
         pub fn get_module() -> HINSTANCE {
             unsafe { *MODULE.get().unwrap() }
@@ -274,9 +476,32 @@ pub mod reexports {
 ///
 /// hudhook::hudhook!(MyRenderLoop.into_hook::<ImguiDx12Hooks>());
 /// ```
+///
+/// Pass a [`HookConfig`](crate::lifecycle::HookConfig) as a second
+/// argument to control how long the hook thread waits for the target's
+/// render DLL to load before installing the trampoline:
+///
+/// ```no_run
+/// # use hudhook::hooks::dx12::ImguiDx12Hooks;
+/// # use hudhook::hooks::{ImguiRenderLoop, ImguiRenderLoopFlags};
+/// # use hudhook::lifecycle::HookConfig;
+/// # use hudhook::*;
+/// # use std::time::Duration;
+/// # pub struct MyRenderLoop;
+/// # impl ImguiRenderLoop for MyRenderLoop {
+/// #     fn render(&mut self, frame: &mut imgui::Ui, flags: &ImguiRenderLoopFlags) {}
+/// # }
+/// hudhook::hudhook!(
+///     MyRenderLoop.into_hook::<ImguiDx12Hooks>(),
+///     HookConfig { max_retries: 1200, retry_interval: Duration::from_millis(50) }
+/// );
+/// ```
 #[macro_export]
 macro_rules! hudhook {
     ($hooks:expr) => {
+        $crate::hudhook!($hooks, $crate::lifecycle::HookConfig::default());
+    };
+    ($hooks:expr, $config:expr) => {
         use hudhook::reexports::*;
         use hudhook::tracing::*;
         use hudhook::*;
@@ -292,9 +517,18 @@ macro_rules! hudhook {
                 hudhook::lifecycle::global_state::set_module(hmodule);
 
                 trace!("DllMain()");
+                let config: hudhook::lifecycle::HookConfig = { $config };
                 std::thread::spawn(move || {
+                    // Wait for the target's renderer to materialize before
+                    // trying to discover its vtables; bail out if it never
+                    // shows up within the configured retry budget.
+                    if !hudhook::lifecycle::wait_for_renderer(&config) {
+                        return;
+                    }
+
                     let hooks: Box<dyn hooks::Hooks> = { $hooks };
                     hooks.hook();
+                    hudhook::lifecycle::global_state::pin_modules();
                     hudhook::lifecycle::global_state::set_hooks(hooks);
                 });
             }